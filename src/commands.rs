@@ -1,20 +1,20 @@
 use {
     super::*,
     fehler::throws,
-    std::{
-        io::{stdout, Write},
-        time::Duration,
-    },
+    std::time::Duration,
+    tracing::Instrument,
 };
 
 /// Adds a new board. Expects the name of the board and either the URL
 /// of the board's RSS feed or the last path segment of the board's URL.
 ///
 /// If `default_user` is not specified in `cfg`, it also expects a user
-/// to be passed to the `--user` option before any other arguments.
+/// to be passed to the `--user` option before any other arguments. A
+/// `--kind` option selects the `Source` the board is fetched through
+/// (e.g. `pinterest`), defaulting to Pinterest.
 #[throws]
 pub fn add(cfg: &mut config::Config, args: impl Iterator<Item = String>) {
-    parse::add(cfg, args, None, None, None)?;
+    parse::add(cfg, args, None, None, None, None)?;
 }
 
 #[throws]
@@ -27,15 +27,19 @@ pub async fn start(cfg: &config::Config) {
         for board in boards {
             let mut cushion = cushion::Cushion::load(&cfg, user, board).await?;
             let mut rx = rx.clone();
-            let handle = tokio::spawn(async move {
-                while let Some(true) = rx.recv().await {
-                    if let Err(e) = cushion.update().await {
-                        eprintln!("something went wrong, re-trying next time: {:?}", e);
+            let span = tracing::info_span!("board", user = %user, board = %board);
+            let handle = tokio::spawn(
+                async move {
+                    while let Some(true) = rx.recv().await {
+                        if let Err(e) = cushion.update().await {
+                            tracing::warn!(error = ?e, "something went wrong, re-trying next time");
+                        }
                     }
+                    tracing::info!("cancelling task");
+                    return Ok(()) as Result<_, Error>;
                 }
-                println!("cancelling task");
-                return Ok(()) as Result<_, Error>;
-            });
+                .instrument(span),
+            );
             handles.push(handle);
         }
     }
@@ -45,8 +49,7 @@ pub async fn start(cfg: &config::Config) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(60));
         loop {
-            print!("listening...\r");
-            stdout().flush().expect("couldn't flush to stdout.");
+            tracing::trace!("listening...");
             tokio::select! {
                 _ = interval.tick() => {
                     let _ = tx.broadcast(true);
@@ -77,6 +80,42 @@ pub async fn start(cfg: &config::Config) {
     }
 }
 
+/// Re-uploads every board's already-downloaded Pins to Imgur, so users
+/// have an off-Pinterest mirror. Requires `imgur_client_id` to be set
+/// in `cfg`.
+///
+/// A board that fails to load or mirror (e.g. one that was `add`ed but
+/// never `start`ed, so it has no state on disk yet) is logged and
+/// skipped rather than aborting the rest of the boards.
+#[throws]
+pub async fn mirror(cfg: &config::Config) {
+    let client_id = cfg
+        .imgur_client_id()
+        .ok_or(Error::MissingImgurClientIdError)?;
+    let imgur = imgur::ImgurClient::new(client_id.to_string());
+
+    for (user, boards) in cfg.boards() {
+        for board in boards {
+            let mut cushion = match cushion::Cushion::load(cfg, user, board).await {
+                Ok(cushion) => cushion,
+                Err(e) => {
+                    tracing::warn!(user = %user, board = %board, error = ?e, "failed to load board, skipping mirror");
+                    continue;
+                }
+            };
+
+            match cushion.mirror(&imgur).await {
+                Ok(mirrored) => {
+                    tracing::info!(user = %user, board = %board, mirrored, "mirrored board to imgur")
+                }
+                Err(e) => {
+                    tracing::warn!(user = %user, board = %board, error = ?e, "failed to mirror board, skipping")
+                }
+            }
+        }
+    }
+}
+
 mod parse {
     use super::*;
 
@@ -87,13 +126,16 @@ mod parse {
         mut user: Option<String>,
         mut board: Option<String>,
         mut url: Option<String>,
+        mut kind: Option<String>,
     ) {
         if let (Some(user), Some(board), Some(url)) = (&user, &board, &url) {
-            cfg.add_board(user, board, url)?;
+            cfg.add_board(user, board, url, kind.as_deref())?;
             return;
         }
         let arg = args.next().ok_or(Error::MissingArgumentsError)?;
-        if user.is_none() {
+        if arg == "--kind" {
+            kind = args.next();
+        } else if user.is_none() {
             if arg == "--user" {
                 user = args.next();
             } else {
@@ -109,7 +151,7 @@ mod parse {
         } else if url.is_none() {
             url.replace(arg);
         }
-        add(cfg, args, user, board, url)?;
+        add(cfg, args, user, board, url, kind)?;
     }
 
     #[throws]
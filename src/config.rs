@@ -1,11 +1,12 @@
 use {
-    super::{cushion, Error},
+    super::{cushion, store, Error},
     dirs,
     fehler::throws,
     serde::{Deserialize, Serialize},
     std::{
         collections::HashMap,
         path::{Path, PathBuf},
+        sync::Arc,
     },
     tokio::{
         fs,
@@ -20,6 +21,30 @@ pub struct Config {
     pin_dir: String,
     default_user: Option<String>,
     boards: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    store: StoreConfig,
+    #[serde(default)]
+    imgur_client_id: Option<String>,
+}
+
+/// Selects which `Store` implementation backs every board, so pins
+/// can be archived to a bucket instead of a local directory.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum StoreConfig {
+    File,
+    S3 {
+        bucket: String,
+        region: String,
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig::File
+    }
 }
 
 impl Config {
@@ -40,6 +65,8 @@ impl Config {
             pin_dir,
             default_user: None,
             boards: HashMap::new(),
+            store: StoreConfig::default(),
+            imgur_client_id: None,
         }
     }
 
@@ -51,12 +78,24 @@ impl Config {
     }
 
     #[throws]
-    pub fn add_board(&mut self, user: &str, board: &str, url: &str) -> cushion::Cushion {
+    pub fn add_board(
+        &mut self,
+        user: &str,
+        board: &str,
+        url: &str,
+        kind: Option<&str>,
+    ) -> cushion::Cushion {
         self.boards
             .entry(user.to_string())
             .or_default()
             .push(board.to_string());
-        cushion::Cushion::new(&self, user.to_string(), board.to_string(), url.to_string())?
+        cushion::Cushion::new(
+            &self,
+            user.to_string(),
+            board.to_string(),
+            url.to_string(),
+            kind,
+        )?
     }
 
     #[throws]
@@ -98,4 +137,31 @@ impl Config {
     pub fn pin_dir(&self) -> &Path {
         &self.pin_dir.as_ref()
     }
+
+    pub fn imgur_client_id(&self) -> Option<&str> {
+        self.imgur_client_id.as_deref()
+    }
+
+    /// Builds the storage backend for `user`/`board`, using `local_path`
+    /// (`<pin_dir>/<user>/<board>`) as the root when storing to the
+    /// local filesystem.
+    #[throws]
+    pub fn build_store(&self, user: &str, board: &str, local_path: &Path) -> Arc<dyn store::Store> {
+        match &self.store {
+            StoreConfig::File => Arc::new(store::FileStore::new(local_path.to_path_buf())),
+            StoreConfig::S3 {
+                bucket,
+                region,
+                prefix,
+            } => {
+                let region = region.parse().map_err(|_| {
+                    Error::StoreError(format!("invalid S3 region: {}", region))
+                })?;
+                let mut key_prefix = PathBuf::from(prefix.clone().unwrap_or_default());
+                key_prefix.push(user);
+                key_prefix.push(board);
+                Arc::new(store::S3Store::new(bucket.clone(), region, key_prefix))
+            }
+        }
+    }
 }
@@ -1,18 +1,34 @@
 use {
-    super::{config::Config, download, Error},
+    super::{
+        config::Config,
+        download,
+        imgur::ImgurClient,
+        queue::{Queue, Task},
+        source::{Source, SourceKind},
+        store::{FileStore, Store},
+        Error,
+    },
     fehler::throws,
     reqwest::Client,
     serde::{Deserialize, Serialize},
-    std::path::{Path, PathBuf},
-    tokio::{fs, io::AsyncWriteExt, stream::StreamExt},
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+    tokio::stream::StreamExt,
 };
 
 const CUSHION_PATH: &str = ".cushion.toml";
 
-/// A `Cushion` manages the state of a Pinterest board.
+/// A `Cushion` manages the state of a board subscription.
 /// It stores the owner, the name, and the url of the board,
 /// as well as the latest Pin that has been downloaded.
 ///
+/// It also carries a `Queue` of board refreshes and Pin downloads that
+/// failed, so they can be retried with backoff instead of being
+/// silently dropped.
+///
 /// `Cushion` implements `Drop` and is saved to disk everytime
 /// it gets dropped.
 #[derive(Serialize, Deserialize)]
@@ -22,24 +38,47 @@ pub struct Cushion {
     url: String,
     path: PathBuf,
     latest_download: String,
+    kind: SourceKind,
+    #[serde(default)]
+    queue: Queue,
+    /// Maps a downloaded Pin's file name to the Imgur link it was
+    /// mirrored to, so `mirror` doesn't re-upload the same Pin twice.
+    #[serde(default)]
+    mirrored: HashMap<String, String>,
     #[serde(skip)]
     client: Client,
+    #[serde(skip, default = "default_store")]
+    store: Arc<dyn Store>,
+    #[serde(skip, default = "default_source")]
+    source: Arc<dyn Source>,
+}
+
+/// Placeholder `Store` used to satisfy deserialization; it is always
+/// replaced with the real backend right after loading.
+fn default_store() -> Arc<dyn Store> {
+    Arc::new(FileStore::new(PathBuf::new()))
+}
+
+/// Placeholder `Source` used to satisfy deserialization; it is always
+/// replaced with the backend matching `kind` right after loading.
+fn default_source() -> Arc<dyn Source> {
+    SourceKind::default().build()
 }
 
 impl Cushion {
     /// Constructs a new `Cushion` for a board found at `url`.
     ///
-    /// `user` is the owner of the board and `board` is its name.
+    /// `user` is the owner of the board and `board` is its name. `kind`
+    /// selects the `Source` the board is fetched through, defaulting to
+    /// Pinterest.
     #[throws]
-    pub fn new(cfg: &Config, user: String, board: String, url: String) -> Self {
+    pub fn new(cfg: &Config, user: String, board: String, url: String, kind: Option<&str>) -> Self {
         let path = Self::construct_path(cfg, &user, &board);
-        std::fs::create_dir_all(&path)?;
+        let store = cfg.build_store(&user, &board, &path)?;
+        let kind = SourceKind::parse(kind)?;
+        let source = kind.build();
 
-        let url: String = if url.starts_with("https://www.pinterest") && url.ends_with(".rss") {
-            url
-        } else {
-            format!("https://www.pinterest.com/{}/{}.rss", &user, &url)
-        };
+        let url = source.feed_url(&user, &url);
 
         let cushion = Self {
             user,
@@ -47,21 +86,53 @@ impl Cushion {
             url,
             path,
             latest_download: Default::default(),
+            kind,
+            queue: Queue::default(),
+            mirrored: HashMap::new(),
             client: Client::new(),
+            store,
+            source,
         };
 
         cushion
     }
 
-    /// Updates the cushion, fetching the rss feed and downloading any newly added Pins.
+    /// Updates the cushion, draining any work left over from a
+    /// previous failure before fetching the rss feed and downloading
+    /// any newly added Pins.
     ///
-    /// Returns how many Pins were downloaded.
+    /// Returns how many Pins were downloaded. A failed feed refresh is
+    /// queued for a later retry with backoff rather than propagated,
+    /// since the queue is now responsible for trying again. If a
+    /// `RefreshBoard` retry was already due and attempted by
+    /// `drain_queue`, that attempt counts as this tick's refresh and
+    /// no second one is made.
     #[throws]
     pub async fn update(&mut self) -> u32 {
+        if let Some(downloaded) = self.drain_queue().await? {
+            return downloaded;
+        }
+
+        match self.refresh().await {
+            Ok(downloaded) => downloaded,
+            Err(e) => {
+                tracing::warn!(error = ?e, "board refresh failed, queued for retry");
+                self.queue.reschedule_task(Task::RefreshBoard);
+                self.save().await?;
+                0
+            }
+        }
+    }
+
+    /// Fetches the rss feed and downloads any newly added Pins.
+    #[throws]
+    async fn refresh(&mut self) -> u32 {
         let resp = self.client.get(&self.url).send().await?.bytes().await?;
         let channel = rss::Channel::read_from(&resp[..])?;
         let latest = self.latest_download.clone();
         let mut downloaded = 0;
+        let mut scanned = 0;
+        let mut failures = Vec::new();
         let items = channel
             .items()
             .iter()
@@ -70,9 +141,12 @@ impl Cushion {
         let mut item_stream = tokio::stream::iter(items);
 
         if let Some(item) = item_stream.next().await {
-            if self.download(item).await? {
+            scanned += 1;
+            let (ok, retry) = self.download(item).await?;
+            if ok {
                 downloaded += 1;
             }
+            failures.extend(retry);
 
             self.latest_download = item
                 .guid()
@@ -82,58 +156,170 @@ impl Cushion {
 
         let mut item_stream = item_stream.map(|item| self.download(item));
         while let Some(b) = item_stream.next().await {
-            if let Ok(true) = b.await {
-                downloaded += 1;
+            scanned += 1;
+            if let Ok((ok, retry)) = b.await {
+                if ok {
+                    downloaded += 1;
+                }
+                failures.extend(retry);
             }
         }
 
-        if downloaded >= 1 {
+        tracing::info!(
+            feed_url = %self.url,
+            items_scanned = scanned,
+            pins_downloaded = downloaded,
+            "board updated"
+        );
+
+        for task in failures {
+            self.queue.reschedule_task(task);
+        }
+
+        if downloaded >= 1 || !self.queue.is_empty() {
             self.save().await?;
         }
 
         downloaded
     }
 
-    /// Download a Pin.
+    /// Download a Pin. Returns whether it succeeded and, if not, the
+    /// `Task` that should be queued for a later retry.
     #[throws]
-    async fn download(&self, item: &rss::Item) -> bool {
-        download::download_pin(
-            &self.client,
-            self.path(),
-            item.description().ok_or(Error::MissingDescriptionError)?,
-        )
-        .await?
+    async fn download(&self, item: &rss::Item) -> (bool, Option<Task>) {
+        let post = self
+            .source
+            .parse_item(item)
+            .ok_or(Error::MissingDescriptionError)?;
+        let guid = item
+            .guid()
+            .map(rss::Guid::value)
+            .unwrap_or_default()
+            .to_string();
+
+        match download::download_pin(&self.client, self.store.as_ref(), &post.url).await {
+            Ok(true) => (true, None),
+            Ok(false) => {
+                tracing::warn!(guid = %guid, url = %post.url, "failed to download pin, queued for retry");
+                (false, Some(Task::DownloadPin { guid, url: post.url }))
+            }
+            Err(e) => {
+                tracing::warn!(guid = %guid, url = %post.url, error = ?e, "failed to download pin, queued for retry");
+                (false, Some(Task::DownloadPin { guid, url: post.url }))
+            }
+        }
     }
 
-    /// Saves this cushion to disk at `self.save_path()`.
+    /// Drains any board refreshes or Pin downloads left pending from a
+    /// previous failure, retrying each. Anything that fails again is
+    /// rescheduled with backoff.
+    ///
+    /// Returns `Some(downloaded)` if a `RefreshBoard` retry was due and
+    /// attempted here, so the caller doesn't also take the direct
+    /// `refresh` path and end up with two live refresh attempts (and
+    /// two freshly-reset `RefreshBoard` queue entries) in the same
+    /// tick. Returns `None` if no `RefreshBoard` was due.
     #[throws]
-    pub async fn save(&self) {
-        let mut save_file = fs::File::create(self.save_path()).await?;
-        let toml_str = toml::to_string_pretty(&self)?;
-        save_file.write(toml_str.as_bytes()).await?;
-        save_file.flush().await?;
+    async fn drain_queue(&mut self) -> Option<u32> {
+        let due = self.queue.drain_due();
+        if due.is_empty() {
+            return None;
+        }
+
+        let mut refreshed = None;
+
+        for item in due {
+            let ok = match &item.task {
+                Task::RefreshBoard => match self.refresh().await {
+                    Ok(downloaded) => {
+                        refreshed = Some(downloaded);
+                        true
+                    }
+                    Err(_) => {
+                        refreshed = Some(0);
+                        false
+                    }
+                },
+                Task::DownloadPin { url, .. } => matches!(
+                    download::download_pin(&self.client, self.store.as_ref(), url).await,
+                    Ok(true)
+                ),
+            };
+
+            if !ok {
+                self.queue.reschedule(item);
+            }
+        }
+
+        self.save().await?;
+
+        refreshed
     }
 
-    /// Loads a cushion from disk at `<pin_dir>/<user>/<board>/.cushion.toml`
-    /// where `<pin_dir>` is specified in `cfg`.
+    /// Re-uploads every already-downloaded Pin that hasn't been
+    /// mirrored yet to Imgur, recording the resulting link so later
+    /// runs don't upload it again.
+    ///
+    /// Returns how many Pins were newly mirrored. This goes through
+    /// `Store` rather than the local filesystem directly, so it works
+    /// for boards archived to a bucket and simply mirrors zero Pins
+    /// for a board that hasn't downloaded anything yet.
     #[throws]
-    pub async fn load(cfg: &Config, user: &str, board: &str) -> Self {
-        let mut path = Self::construct_path(cfg, user, board);
-        path.reserve_exact(1);
-        path.push(CUSHION_PATH);
-        let toml_str = fs::read_to_string(path).await?;
-        toml::from_str(&toml_str)?
+    pub async fn mirror(&mut self, imgur: &ImgurClient) -> u32 {
+        let mut mirrored_count = 0;
+
+        for key in self.store.list().await? {
+            let file_name = key
+                .to_str()
+                .ok_or_else(|| Error::StoreError("non-UTF-8 file name".to_string()))?
+                .to_string();
+
+            if file_name == CUSHION_PATH || self.mirrored.contains_key(&file_name) {
+                continue;
+            }
+
+            let bytes = self.store.get(&key).await?;
+            match imgur.upload(&bytes).await {
+                Ok(link) => {
+                    tracing::info!(file_name = %file_name, link = %link, "mirrored pin to imgur");
+                    self.mirrored.insert(file_name, link);
+                    mirrored_count += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(file_name = %file_name, error = ?e, "failed to mirror pin to imgur");
+                }
+            }
+        }
+
+        if mirrored_count > 0 {
+            self.save().await?;
+        }
+
+        mirrored_count
     }
 
-    /// Return the location of this cushion's board on disk.
-    fn path(&self) -> &Path {
-        &self.path
+    /// Saves this cushion to its store at `CUSHION_PATH`.
+    #[throws]
+    pub async fn save(&self) {
+        let toml_str = toml::to_string_pretty(&self)?;
+        self.store
+            .put(Path::new(CUSHION_PATH), toml_str.as_bytes())
+            .await?;
     }
 
-    /// Like `path` but appends the actual filepath of this cushion
-    /// on disk.
-    fn save_path(&self) -> impl AsRef<Path> {
-        self.path.join(CUSHION_PATH)
+    /// Loads a cushion from its store at `<user>/<board>/.cushion.toml`
+    /// where the store's root is derived from `cfg`.
+    #[throws]
+    pub async fn load(cfg: &Config, user: &str, board: &str) -> Self {
+        let path = Self::construct_path(cfg, user, board);
+        let store = cfg.build_store(user, board, &path)?;
+        let bytes = store.get(Path::new(CUSHION_PATH)).await?;
+        let toml_str = String::from_utf8(bytes)
+            .map_err(|_| Error::StoreError("cushion state is not valid UTF-8".to_string()))?;
+        let mut cushion: Self = toml::from_str(&toml_str)?;
+        cushion.source = cushion.kind.build();
+        cushion.store = store;
+        cushion
     }
 
     /// Constructs the path `<pin_dir>/<user>/<board>` where `<pin_dir>`
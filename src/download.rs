@@ -1,139 +1,148 @@
 use {
-    super::Error,
+    super::{store::Store, Error},
     fehler::throws,
-    lazy_static::lazy_static,
-    regex::Regex,
-    reqwest::Client,
-    std::{fs, io::prelude::*, path::Path},
+    reqwest::{header::CONTENT_TYPE, Client},
+    std::path::Path,
 };
 
-/// Downloads a Pin. `pin_description` is the description
-/// field of an `item` in a board's RSS channel. Returns
+/// Downloads a Pin whose original image lives at `url_base` (sans file
+/// extension, as normalised by a `Source`). The true format is
+/// determined from the response rather than guessed up front. Returns
 /// whether it was succesful.
 #[throws]
-pub async fn download_pin(client: &Client, dir: impl AsRef<Path>, pin_description: &str) -> bool {
-    let url_base = match url_base_from_description(pin_description) {
-        Some(url) => url,
-        None => return false,
+pub async fn download_pin(client: &Client, store: &dyn Store, url_base: &str) -> bool {
+    let url = url_base.trim_end_matches('.');
+
+    let resp = match client.get(url).send().await {
+        Ok(resp) if resp.status().is_success() => resp,
+        Ok(resp) => {
+            tracing::warn!(url = %url, status = %resp.status(), "pin request failed");
+            return false;
+        }
+        Err(e) => {
+            tracing::warn!(url = %url, error = %e, "pin request failed");
+            return false;
+        }
     };
 
-    // A Pin's thumbnail is always a .jpg, whereas the original
-    // might have any file extension. We iterate over the most
-    // common in the hope of getting the right one.
-    for &ext in &file_extensions::EXTENSIONS {
-        let url = url_base_with_extension(&url_base, ext);
-        if let Ok(resp) = client.get(&url).send().await {
-            if !resp.status().is_success() {
-                continue;
-            }
-            let body = resp.bytes().await?;
-            let file_name = url
-                .rsplit('/')
-                .next()
-                .expect("url needs at least one segment.");
-
-            println!("Getting: {}", file_name);
-
-            let mut file = fs::File::create(&dir.as_ref().join(file_name))?;
-            file.write_all(&body[..])?;
-            return true;
-        }
-    }
-    false
-}
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(image_format::from_content_type);
 
-/// Normalises the content from a Pin's description into the
-/// URL of the original image sans the file extension.
-fn url_base_from_description(descr: &str) -> Option<String> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r#"(?m)img src="(https://i.pinimg.com)/\S*?/(\S*\.).*""#)
-            .expect("regex needs to compile");
-    }
+    let body = resp.bytes().await?;
 
-    RE.captures(descr)
-        .map(|caps| format!("{}/originals/{}", &caps[1], &caps[2]))
-}
+    let format = match content_type.or_else(|| image_format::sniff(&body)) {
+        Some(format) => format,
+        None => return false,
+    };
 
-/// Takes a URL normalised with `url_base_from_description` and
-/// appends the file extension `ext`.
-fn url_base_with_extension(base: &str, ext: file_extensions::Extension) -> String {
-    format!("{}{}", base, ext)
-}
+    let stem = url
+        .rsplit('/')
+        .next()
+        .expect("url needs at least one segment.");
+    let file_name = format!("{}.{}", stem, format);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_url_replace() {
-        let tests = [
-            (
-                r#"<a href="https://www.pinterest.de/pin/534872893249331184/"> <img src="https://i.pinimg.com/236x/e1/5f/eb/e15feb255af25743320bc495f85e3e28.jpg"></a>"#,
-                Some(String::from(
-                    r#"https://i.pinimg.com/originals/e1/5f/eb/e15feb255af25743320bc495f85e3e28."#,
-                )),
-            ),
-            (
-                r#"<a href="https://www.pinterest.de/pin/534872893249331052/"> <img src="https://i.pinimg.com/236x/80/bb/34/80bb34fc6ed85a445ee5f1b89ffa407e.jpg"></a>"#,
-                Some(String::from(
-                    r#"https://i.pinimg.com/originals/80/bb/34/80bb34fc6ed85a445ee5f1b89ffa407e."#,
-                )),
-            ),
-            (
-                r#"<a href="https://www.pinterest.de/pin/534872893249331052/"></a>"#,
-                None,
-            ),
-            (
-                "<a href=\"https://www.pinterest.de/pin/534872893249340309/\">\n                  <img src=\"https://i.pinimg.com/236x/e3/b9/21/e3b9217c7e67f8891d2ff7ba7a0a4fe3.jpg\"></a>",
-                Some(String::from("https://i.pinimg.com/originals/e3/b9/21/e3b9217c7e67f8891d2ff7ba7a0a4fe3."))
-            ),
-        ];
-
-        for (test, want) in &tests {
-            assert_eq!(url_base_from_description(test), *want);
-        }
-    }
+    tracing::info!(file_name = %file_name, "downloading pin");
+
+    store.put(Path::new(&file_name), &body[..]).await?;
+    true
 }
 
-/// Provides the most common file formats a Pin's image might have.
-mod file_extensions {
+/// Determines an image's format from its `Content-Type` header or,
+/// failing that, the magic bytes at the start of its body.
+mod image_format {
     use std::fmt;
-    use Extension::*;
-
-    /// Contains the most common file extensions.
-    pub const EXTENSIONS: [Extension; 8] = [Jpg, Jpeg, Png, Webm, Tiff, Gif, Jfif, Jiff];
 
-    /// Enumerates the most common file extensions. When a new variant is added,
-    /// it has to be added to the `EXTENSIONS` array as well.
-    #[forbid(dead_code)]
+    /// Enumerates the image formats a Pin's original can have.
     #[derive(Clone, Copy)]
-    pub enum Extension {
+    pub enum Format {
         Jpg,
-        Jpeg,
         Png,
-        Webm,
-        Tiff,
         Gif,
-        Jfif,
-        Jiff,
+        Webp,
+        Tiff,
     }
 
-    impl fmt::Display for Extension {
+    impl fmt::Display for Format {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            use Extension::*;
+            use Format::*;
 
             let s = match self {
                 Jpg => "jpg",
-                Jpeg => "jpeg",
                 Png => "png",
-                Webm => "webm",
-                Tiff => "tiff",
                 Gif => "gif",
-                Jfif => "jfif",
-                Jiff => "jiff",
+                Webp => "webp",
+                Tiff => "tiff",
             };
 
             f.write_str(s)
         }
     }
+
+    /// Maps a `Content-Type` header value to a `Format`, ignoring any
+    /// trailing `; charset=...` parameters.
+    pub fn from_content_type(content_type: &str) -> Option<Format> {
+        match content_type.split(';').next()?.trim() {
+            "image/jpeg" => Some(Format::Jpg),
+            "image/png" => Some(Format::Png),
+            "image/gif" => Some(Format::Gif),
+            "image/webp" => Some(Format::Webp),
+            "image/tiff" => Some(Format::Tiff),
+            _ => None,
+        }
+    }
+
+    /// Sniffs the leading bytes of `body` for a recognised image
+    /// format's magic number.
+    pub fn sniff(body: &[u8]) -> Option<Format> {
+        if body.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Format::Jpg)
+        } else if body.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(Format::Png)
+        } else if body.starts_with(b"GIF87a") || body.starts_with(b"GIF89a") {
+            Some(Format::Gif)
+        } else if body.len() >= 12 && &body[0..4] == b"RIFF" && &body[8..12] == b"WEBP" {
+            Some(Format::Webp)
+        } else if body.starts_with(&[0x49, 0x49, 0x2A, 0x00]) || body.starts_with(&[0x4D, 0x4D, 0x00, 0x2A]) {
+            Some(Format::Tiff)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_from_content_type() {
+            assert!(matches!(from_content_type("image/jpeg"), Some(Format::Jpg)));
+            assert!(matches!(
+                from_content_type("image/png; charset=binary"),
+                Some(Format::Png)
+            ));
+            assert!(from_content_type("text/html").is_none());
+        }
+
+        #[test]
+        fn test_sniff() {
+            assert!(matches!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(Format::Jpg)));
+            assert!(matches!(
+                sniff(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]),
+                Some(Format::Png)
+            ));
+            assert!(matches!(sniff(b"GIF89a..."), Some(Format::Gif)));
+            assert!(matches!(
+                sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+                Some(Format::Webp)
+            ));
+            assert!(matches!(
+                sniff(&[0x49, 0x49, 0x2A, 0x00]),
+                Some(Format::Tiff)
+            ));
+            assert!(sniff(b"not an image").is_none());
+        }
+    }
 }
@@ -0,0 +1,64 @@
+use {
+    super::Error,
+    fehler::{throw, throws},
+    reqwest::{header::AUTHORIZATION, Client},
+    serde::Deserialize,
+};
+
+const UPLOAD_URL: &str = "https://api.imgur.com/3/image";
+
+/// Re-hosts Pins on Imgur, so users have an off-Pinterest mirror.
+pub struct ImgurClient {
+    client_id: String,
+    client: Client,
+}
+
+impl ImgurClient {
+    pub fn new(client_id: String) -> Self {
+        Self {
+            client_id,
+            client: Client::new(),
+        }
+    }
+
+    /// Uploads `bytes` anonymously under this client's `client_id` and
+    /// returns the resulting image's link.
+    #[throws]
+    pub async fn upload(&self, bytes: &[u8]) -> String {
+        let encoded = base64::encode(bytes);
+
+        let resp = self
+            .client
+            .post(UPLOAD_URL)
+            .header(AUTHORIZATION, format!("Client-ID {}", self.client_id))
+            .form(&[("image", encoded.as_str())])
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let body: Response = resp
+            .json()
+            .await
+            .map_err(|_| Error::ImgurError(format!("unrecognised response (status {})", status)))?;
+
+        if !status.is_success() || !body.success {
+            throw!(Error::ImgurError(format!(
+                "upload failed with status {}",
+                status
+            )));
+        }
+
+        body.data.link
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    success: bool,
+    data: Data,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    link: String,
+}
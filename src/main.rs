@@ -5,9 +5,17 @@ mod commands;
 mod config;
 mod cushion;
 mod download;
+mod imgur;
+mod queue;
+mod source;
+mod store;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
     let mut args = std::env::args().skip(1);
     let cmd = args.next().ok_or(Error::MissingArgumentsError)?;
     let mut cfg = config::Config::load().await;
@@ -15,6 +23,7 @@ async fn main() -> Result<(), Error> {
         Ok(ref mut cfg) => match cmd.as_ref() {
             "add" => commands::add(cfg, args)?,
             "start" => commands::start(&cfg).await?,
+            "mirror" => commands::mirror(&cfg).await?,
             _ => throw!(Error::InvalidArgument),
         },
         Err(Error::IoError(_)) => match cmd.as_ref() {
@@ -45,6 +54,9 @@ mod error {
         MissingArgumentsError,
         InvalidArgument,
         UninitError,
+        StoreError(String),
+        MissingImgurClientIdError,
+        ImgurError(String),
     }
 
     impl From<toml::ser::Error> for Error {
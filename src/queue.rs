@@ -0,0 +1,124 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Base delay before the first retry of a failed item.
+const BASE_BACKOFF_SECS: u64 = 60;
+/// Upper bound on the exponential backoff delay.
+const MAX_BACKOFF_SECS: u64 = 60 * 60;
+/// Number of attempts after which an item is moved to the dead letters
+/// instead of being retried again.
+const MAX_ATTEMPTS: u32 = 8;
+
+/// A unit of work a `Cushion` couldn't finish on the spot.
+#[derive(Serialize, Deserialize)]
+pub enum Task {
+    /// The board's feed needs to be re-fetched and parsed.
+    RefreshBoard,
+    /// A single Pin, identified by its RSS `guid`, needs downloading.
+    DownloadPin { guid: String, url: String },
+}
+
+/// A `Task` together with its retry bookkeeping.
+///
+/// `task` is declared last: toml only allows table-shaped values (like
+/// `Task::DownloadPin`) after every scalar field in the struct, and
+/// serializing a `QueueItem` ahead of that fix panics with
+/// `UnsupportedType` for every variant but the unit-like
+/// `Task::RefreshBoard`.
+#[derive(Serialize, Deserialize)]
+pub struct QueueItem {
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub task: Task,
+}
+
+impl QueueItem {
+    fn new(task: Task) -> Self {
+        Self {
+            attempts: 0,
+            next_attempt_at: now(),
+            task,
+        }
+    }
+}
+
+/// A persisted queue of pending board refreshes and Pin downloads.
+///
+/// Because it is serialized alongside a `Cushion`'s state, work that
+/// fails is never silently dropped: it survives process restarts and
+/// is retried with exponential backoff until it succeeds or exhausts
+/// `MAX_ATTEMPTS`, at which point it is moved to `dead_letters`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Queue {
+    items: Vec<QueueItem>,
+    dead_letters: Vec<QueueItem>,
+}
+
+impl Queue {
+    /// Removes and returns every item whose `next_attempt_at` has
+    /// passed. Callers are responsible for calling `reschedule` on any
+    /// item that fails again.
+    pub fn drain_due(&mut self) -> Vec<QueueItem> {
+        let now = now();
+        let (due, pending): (Vec<_>, Vec<_>) =
+            self.items.drain(..).partition(|item| item.next_attempt_at <= now);
+        self.items = pending;
+        due
+    }
+
+    /// Wraps `task` in a fresh `QueueItem` and reschedules it, as if its
+    /// first attempt had just failed.
+    pub fn reschedule_task(&mut self, task: Task) {
+        self.reschedule(QueueItem::new(task));
+    }
+
+    /// Reschedules a failed `item`, doubling the backoff per attempt up
+    /// to `MAX_BACKOFF_SECS`. Once it has failed `MAX_ATTEMPTS` times, the
+    /// item is moved to the dead letters instead of being retried again.
+    pub fn reschedule(&mut self, mut item: QueueItem) {
+        item.attempts += 1;
+        if item.attempts >= MAX_ATTEMPTS {
+            self.dead_letters.push(item);
+            return;
+        }
+
+        let backoff =
+            BASE_BACKOFF_SECS.saturating_mul(1u64 << (item.attempts - 1).min(16)).min(MAX_BACKOFF_SECS);
+        item.next_attempt_at = now() + backoff;
+        self.items.push(item);
+    }
+
+    /// Whether there is no pending and no dead-lettered work.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.dead_letters.is_empty()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_queue_with_a_download_pin_item() {
+        let mut queue = Queue::default();
+        queue.reschedule_task(Task::DownloadPin {
+            guid: "some-guid".to_string(),
+            url: "https://i.pinimg.com/originals/some-pin".to_string(),
+        });
+        queue.reschedule_task(Task::RefreshBoard);
+
+        let toml_str = toml::to_string(&queue).expect("queue should serialize");
+        let round_tripped: Queue = toml::from_str(&toml_str).expect("queue should deserialize");
+
+        assert_eq!(round_tripped.items.len(), queue.items.len());
+    }
+}
@@ -0,0 +1,145 @@
+use {
+    super::Error,
+    lazy_static::lazy_static,
+    regex::Regex,
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+};
+
+/// A feed entry normalized across whatever site it came from.
+pub struct PostInfo {
+    pub url: String,
+}
+
+/// A feed `Cushion` can subscribe to. Implementors know how to build a
+/// board's feed URL and how to turn one of its `rss::Item`s into a
+/// normalized `PostInfo`.
+pub trait Source: Send + Sync {
+    /// Builds the feed URL for a user's board/blog. `slug` is whatever
+    /// was passed to `add` -- either a full feed URL or a bare slug.
+    fn feed_url(&self, user: &str, slug: &str) -> String;
+
+    /// Parses one feed item into a normalized `PostInfo`, or `None` if
+    /// the item doesn't contain a recognisable Pin/post.
+    fn parse_item(&self, item: &rss::Item) -> Option<PostInfo>;
+}
+
+/// Selects which `Source` backs a board, serialized alongside the rest
+/// of a `Cushion`'s state so it survives a reload.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Pinterest,
+}
+
+impl Default for SourceKind {
+    fn default() -> Self {
+        SourceKind::Pinterest
+    }
+}
+
+impl SourceKind {
+    /// Parses the `--kind` argument passed to `add`, defaulting to
+    /// Pinterest when none was given.
+    pub fn parse(kind: Option<&str>) -> Result<Self, Error> {
+        match kind.unwrap_or("pinterest") {
+            "pinterest" => Ok(SourceKind::Pinterest),
+            _ => Err(Error::InvalidArgument),
+        }
+    }
+
+    pub fn build(self) -> Arc<dyn Source> {
+        match self {
+            SourceKind::Pinterest => Arc::new(Pinterest),
+        }
+    }
+}
+
+/// The original source: Pinterest board RSS feeds.
+pub struct Pinterest;
+
+impl Source for Pinterest {
+    fn feed_url(&self, user: &str, slug: &str) -> String {
+        if slug.starts_with("https://www.pinterest") && slug.ends_with(".rss") {
+            slug.to_string()
+        } else {
+            format!("https://www.pinterest.com/{}/{}.rss", user, slug)
+        }
+    }
+
+    fn parse_item(&self, item: &rss::Item) -> Option<PostInfo> {
+        let descr = item.description()?;
+        let (_thumb, url) = pinterest_urls_from_description(descr)?;
+
+        Some(PostInfo { url })
+    }
+}
+
+/// Normalises the content from a Pin's description into the thumbnail
+/// URL and the URL of the original image sans the file extension.
+fn pinterest_urls_from_description(descr: &str) -> Option<(String, String)> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r#"(?m)img src="(https://i\.pinimg\.com)/(\S*?)/(\S*\.)(\S*)""#)
+                .expect("regex needs to compile");
+    }
+
+    let caps = RE.captures(descr)?;
+    let domain = &caps[1];
+    let size_dir = &caps[2];
+    let base = &caps[3];
+    let ext = &caps[4];
+
+    let thumb = format!("{}/{}/{}{}", domain, size_dir, base, ext);
+    let url = format!("{}/originals/{}", domain, base);
+
+    Some((thumb, url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_replace() {
+        let tests = [
+            (
+                r#"<a href="https://www.pinterest.de/pin/534872893249331184/"> <img src="https://i.pinimg.com/236x/e1/5f/eb/e15feb255af25743320bc495f85e3e28.jpg"></a>"#,
+                Some((
+                    String::from(
+                        "https://i.pinimg.com/236x/e1/5f/eb/e15feb255af25743320bc495f85e3e28.jpg",
+                    ),
+                    String::from(
+                        r#"https://i.pinimg.com/originals/e1/5f/eb/e15feb255af25743320bc495f85e3e28."#,
+                    ),
+                )),
+            ),
+            (
+                r#"<a href="https://www.pinterest.de/pin/534872893249331052/"> <img src="https://i.pinimg.com/236x/80/bb/34/80bb34fc6ed85a445ee5f1b89ffa407e.jpg"></a>"#,
+                Some((
+                    String::from(
+                        "https://i.pinimg.com/236x/80/bb/34/80bb34fc6ed85a445ee5f1b89ffa407e.jpg",
+                    ),
+                    String::from(
+                        r#"https://i.pinimg.com/originals/80/bb/34/80bb34fc6ed85a445ee5f1b89ffa407e."#,
+                    ),
+                )),
+            ),
+            (
+                r#"<a href="https://www.pinterest.de/pin/534872893249331052/"></a>"#,
+                None,
+            ),
+            (
+                "<a href=\"https://www.pinterest.de/pin/534872893249340309/\">\n                  <img src=\"https://i.pinimg.com/236x/e3/b9/21/e3b9217c7e67f8891d2ff7ba7a0a4fe3.jpg\"></a>",
+                Some((
+                    String::from("https://i.pinimg.com/236x/e3/b9/21/e3b9217c7e67f8891d2ff7ba7a0a4fe3.jpg"),
+                    String::from("https://i.pinimg.com/originals/e3/b9/21/e3b9217c7e67f8891d2ff7ba7a0a4fe3."),
+                )),
+            ),
+        ];
+
+        for (test, want) in &tests {
+            assert_eq!(pinterest_urls_from_description(test), *want);
+        }
+    }
+}
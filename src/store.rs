@@ -0,0 +1,196 @@
+use {
+    super::Error,
+    async_trait::async_trait,
+    std::path::{Path, PathBuf},
+    tokio::{fs, io::AsyncWriteExt, stream::StreamExt},
+};
+
+/// A pluggable storage backend for downloaded Pins.
+///
+/// Implementors decide where the bytes behind a `key` actually end up,
+/// be that the local filesystem or a remote bucket. `Cushion` and
+/// `download::download_pin` only ever talk to a `Store`, never to
+/// `std::fs`/`tokio::fs` directly.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Writes `bytes` to `key`, creating any missing parents.
+    async fn put(&self, key: &Path, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Reads the bytes stored at `key`.
+    async fn get(&self, key: &Path) -> Result<Vec<u8>, Error>;
+
+    /// Returns whether `key` already exists in the store.
+    async fn exists(&self, key: &Path) -> Result<bool, Error>;
+
+    /// Lists the keys currently in the store. Nothing having been
+    /// stored yet is not an error; it yields an empty list.
+    async fn list(&self) -> Result<Vec<PathBuf>, Error>;
+}
+
+/// Stores Pins as plain files under a root directory. This is the
+/// behavior `pin-cushion` has always had.
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, key: &Path) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+#[async_trait]
+impl Store for FileStore {
+    async fn put(&self, key: &Path, bytes: &[u8]) -> Result<(), Error> {
+        let path = self.resolve(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let mut file = fs::File::create(path).await?;
+        file.write_all(bytes).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &Path) -> Result<Vec<u8>, Error> {
+        Ok(fs::read(self.resolve(key)).await?)
+    }
+
+    async fn exists(&self, key: &Path) -> Result<bool, Error> {
+        Ok(fs::metadata(self.resolve(key)).await.is_ok())
+    }
+
+    async fn list(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut entries = match fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut keys = Vec::new();
+        while let Some(entry) = entries.next().await {
+            keys.push(PathBuf::from(entry?.file_name()));
+        }
+        Ok(keys)
+    }
+}
+
+/// Stores Pins in an S3-compatible bucket under `prefix`, so a board
+/// can be archived off-disk instead of under `pin_dir`.
+pub struct S3Store {
+    bucket: String,
+    prefix: PathBuf,
+    client: rusoto_s3::S3Client,
+}
+
+impl S3Store {
+    pub fn new(bucket: String, region: rusoto_core::Region, prefix: impl Into<PathBuf>) -> Self {
+        Self {
+            bucket,
+            prefix: prefix.into(),
+            client: rusoto_s3::S3Client::new(region),
+        }
+    }
+
+    fn resolve(&self, key: &Path) -> String {
+        self.prefix.join(key).to_string_lossy().replace('\\', "/")
+    }
+}
+
+#[async_trait]
+impl Store for S3Store {
+    async fn put(&self, key: &Path, bytes: &[u8]) -> Result<(), Error> {
+        use rusoto_s3::{PutObjectRequest, S3};
+
+        self.client
+            .put_object(PutObjectRequest {
+                bucket: self.bucket.clone(),
+                key: self.resolve(key),
+                body: Some(bytes.to_vec().into()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| Error::StoreError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &Path) -> Result<Vec<u8>, Error> {
+        use {rusoto_s3::{GetObjectRequest, S3}, tokio::io::AsyncReadExt};
+
+        let resp = self
+            .client
+            .get_object(GetObjectRequest {
+                bucket: self.bucket.clone(),
+                key: self.resolve(key),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| Error::StoreError(e.to_string()))?;
+
+        let mut buf = Vec::new();
+        resp.body
+            .ok_or_else(|| Error::StoreError("empty S3 response body".to_string()))?
+            .into_async_read()
+            .read_to_end(&mut buf)
+            .await?;
+        Ok(buf)
+    }
+
+    async fn exists(&self, key: &Path) -> Result<bool, Error> {
+        use rusoto_s3::{HeadObjectRequest, S3};
+
+        match self
+            .client
+            .head_object(HeadObjectRequest {
+                bucket: self.bucket.clone(),
+                key: self.resolve(key),
+                ..Default::default()
+            })
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(rusoto_core::RusotoError::Unknown(resp)) if resp.status == 404 => Ok(false),
+            Err(e) => Err(Error::StoreError(e.to_string())),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<PathBuf>, Error> {
+        use rusoto_s3::{ListObjectsV2Request, S3};
+
+        let prefix = self.prefix.to_string_lossy().replace('\\', "/");
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let resp = self
+                .client
+                .list_objects_v2(ListObjectsV2Request {
+                    bucket: self.bucket.clone(),
+                    prefix: Some(prefix.clone()),
+                    continuation_token: continuation_token.take(),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| Error::StoreError(e.to_string()))?;
+
+            for object in resp.contents.unwrap_or_default() {
+                if let Some(key) = object.key {
+                    if let Ok(relative) = Path::new(&key).strip_prefix(&prefix) {
+                        keys.push(relative.to_path_buf());
+                    }
+                }
+            }
+
+            if resp.is_truncated != Some(true) {
+                break;
+            }
+            continuation_token = resp.next_continuation_token;
+        }
+
+        Ok(keys)
+    }
+}